@@ -0,0 +1,24 @@
+extern crate dkregistry;
+extern crate futures;
+
+use futures::prelude::*;
+use std::boxed;
+use std::error::Error;
+
+/// Authenticate a client for a single pull scope.
+pub fn authenticate_client(
+    client: dkregistry::v2::Client,
+    login_scope: String,
+) -> impl Future<Item = dkregistry::v2::Client, Error = boxed::Box<dyn Error>> {
+    client
+        .is_v2_supported()
+        .and_then(move |supported| {
+            if !supported {
+                Err(dkregistry::errors::Error::from("API v2 not supported"))
+            } else {
+                Ok(client)
+            }
+        })
+        .and_then(move |client| client.authenticate(&[login_scope.as_str()]))
+        .map_err(|e| boxed::Box::new(e) as boxed::Box<dyn Error>)
+}
@@ -11,6 +11,20 @@ use std::{boxed, env, error, fs, io};
 
 mod common;
 
+/// Map the Rust host architecture (`std::env::consts::ARCH`) to the GOARCH
+/// name used by Docker/OCI manifest-list `platform.architecture` entries.
+fn host_goarch() -> &'static str {
+    match std::env::consts::ARCH {
+        "x86_64" => "amd64",
+        "aarch64" => "arm64",
+        "arm" => "arm",
+        "x86" => "386",
+        "powerpc64" => "ppc64le",
+        "s390x" => "s390x",
+        other => other,
+    }
+}
+
 fn main() {
     let registry = match std::env::args().nth(1) {
         Some(x) => x,
@@ -93,28 +107,45 @@ fn run(
                 })
         })
         .and_then(|(dclient, manifest_kind)| {
+            use dkregistry::mediatypes::MediaTypes;
             let image = image.clone();
-            dclient.get_manifest(&image, &version).and_then(
-                move |manifest_body| match manifest_kind {
-                    dkregistry::mediatypes::MediaTypes::ManifestV2S1Signed => {
-                        let m: dkregistry::v2::manifest::ManifestSchema1Signed =
-                            match serde_json::from_slice(manifest_body.as_slice()) {
-                                Ok(json) => json,
-                                Err(e) => return Err(e.into()),
-                            };
-                        Ok((dclient, m.get_layers()))
-                    }
-                    dkregistry::mediatypes::MediaTypes::ManifestV2S2 => {
-                        let m: dkregistry::v2::manifest::ManifestSchema2 =
-                            match serde_json::from_slice(manifest_body.as_slice()) {
-                                Ok(json) => json,
-                                Err(e) => return Err(e.into()),
-                            };
-                        Ok((dclient, m.get_layers()))
-                    }
-                    _ => Err("unknown format".into()),
-                },
-            )
+            let version = version.clone();
+            // Multi-arch images expose a manifest list / OCI index at the
+            // top level; resolve it to a per-arch manifest for the host.
+            let body_future: boxed::Box<
+                dyn Future<Item = Vec<u8>, Error = boxed::Box<error::Error>>,
+            > = match manifest_kind {
+                MediaTypes::ManifestList | MediaTypes::OCIImageIndexV1 => boxed::Box::new(
+                    dclient
+                        .get_manifest_for_platform(
+                            &image,
+                            &version,
+                            host_goarch(),
+                            std::env::consts::OS,
+                        )
+                        .from_err(),
+                ),
+                _ => boxed::Box::new(dclient.get_manifest(&image, &version).from_err()),
+            };
+            body_future.and_then(move |manifest_body| match manifest_kind {
+                MediaTypes::ManifestV2S1Signed => {
+                    let m: dkregistry::v2::manifest::ManifestSchema1Signed =
+                        match serde_json::from_slice(manifest_body.as_slice()) {
+                            Ok(json) => json,
+                            Err(e) => return Err(e.into()),
+                        };
+                    Ok((dclient, m.get_layers()))
+                }
+                MediaTypes::ManifestV2S2 | MediaTypes::ManifestList | MediaTypes::OCIImageIndexV1 => {
+                    let m: dkregistry::v2::manifest::ManifestSchema2 =
+                        match serde_json::from_slice(manifest_body.as_slice()) {
+                            Ok(json) => json,
+                            Err(e) => return Err(e.into()),
+                        };
+                    Ok((dclient, m.get_layers()))
+                }
+                _ => Err("unknown format".into()),
+            })
         })
         .and_then(|(dclient, layers)| {
             let image = image.clone();
@@ -123,7 +154,7 @@ fn run(
 
             futures::stream::iter_ok::<_, dkregistry::errors::Error>(layers)
                 .and_then(move |layer| {
-                    let get_blob_future = dclient.get_blob(&image, &layer);
+                    let get_blob_future = dclient.get_verified_blob(&image, &layer);
                     get_blob_future.inspect(move |blob| {
                         println!("Layer {}, got {} bytes.\n", layer, blob.len());
                     })
@@ -0,0 +1,24 @@
+//! Error chains, types and traits.
+
+error_chain! {
+    foreign_links {
+        Base64Decode(::base64::DecodeError);
+        HeaderParse(::reqwest::header::ToStrError);
+        HeaderValue(::reqwest::header::InvalidHeaderValue);
+        Hyper(::reqwest::Error);
+        Io(::std::io::Error);
+        Json(::serde_json::Error);
+        Regex(::regex::Error);
+        Uri(::reqwest::UrlError);
+        Utf8Parse(::std::str::Utf8Error);
+    }
+
+    errors {
+        /// The content digest of a downloaded object did not match the
+        /// expected digest.
+        ContentDigestMismatch(expected: String, found: String) {
+            description("content digest mismatch")
+            display("content digest mismatch: expected {}, got {}", expected, found)
+        }
+    }
+}
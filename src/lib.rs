@@ -0,0 +1,174 @@
+//! A pure-Rust asynchronous library for Docker Registry API v2.
+//!
+//! This crate provides support for asynchronous interaction with
+//! container registries conformant to the Docker Registry HTTP API v2.
+//!
+//! ## Example
+//!
+//! ```rust,no_run
+//! # extern crate dkregistry;
+//! # extern crate tokio;
+//! # fn main() {
+//! use dkregistry::v2::Client;
+//!
+//! let client = Client::configure()
+//!     .registry("quay.io")
+//!     .build()
+//!     .unwrap();
+//! # }
+//! ```
+
+#![recursion_limit = "1024"]
+
+#[macro_use]
+extern crate error_chain;
+#[macro_use]
+extern crate log;
+#[macro_use]
+extern crate serde_derive;
+
+extern crate base64;
+extern crate flate2;
+extern crate futures;
+extern crate mime;
+extern crate regex;
+extern crate reqwest;
+extern crate serde;
+extern crate serde_json;
+extern crate sha2;
+extern crate tar;
+
+pub mod errors;
+pub mod mediatypes;
+pub mod reference;
+pub mod render;
+pub mod v2;
+
+use errors::*;
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::process::{Command, Stdio};
+
+/// User-agent sent along with registry requests.
+pub static USER_AGENT: &str = concat!("dkregistry/", env!("CARGO_PKG_VERSION"));
+
+#[derive(Debug, Default, Deserialize)]
+struct DockerConfig {
+    #[serde(default)]
+    auths: HashMap<String, DockerAuth>,
+    #[serde(rename = "credsStore", default)]
+    creds_store: Option<String>,
+    #[serde(rename = "credHelpers", default)]
+    cred_helpers: HashMap<String, String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct DockerAuth {
+    #[serde(default)]
+    auth: String,
+}
+
+/// Reply produced by a `docker-credential-<helper>` binary.
+#[derive(Debug, Default, Deserialize)]
+struct HelperReply {
+    #[serde(rename = "Username", default)]
+    username: String,
+    #[serde(rename = "Secret", default)]
+    secret: String,
+}
+
+/// Parse docker credentials for a registry out of a `config.json` reader.
+///
+/// A per-registry `credHelpers` entry takes precedence, then the
+/// top-level `credsStore`; either resolves to a `docker-credential-<helper>`
+/// binary on `PATH` that is queried for the credentials. When no helper is
+/// configured, the base64-encoded `auths.<registry>.auth` field is used as
+/// a fallback.
+pub fn get_credentials<T: Read>(
+    reader: T,
+    index: &str,
+) -> Result<(Option<String>, Option<String>)> {
+    let config: DockerConfig = serde_json::from_reader(reader)?;
+
+    let helper = config
+        .cred_helpers
+        .get(index)
+        .or_else(|| config.creds_store.as_ref());
+    if let Some(helper) = helper {
+        return credentials_from_helper(helper, index);
+    }
+
+    let auth = match config.auths.get(index) {
+        Some(a) if !a.auth.is_empty() => &a.auth,
+        _ => return Ok((None, None)),
+    };
+    decode_auth(auth)
+}
+
+/// Split a base64 `user:password` auth token into its components.
+fn decode_auth(auth: &str) -> Result<(Option<String>, Option<String>)> {
+    let decoded = base64::decode(auth)?;
+    let plain = String::from_utf8(decoded).chain_err(|| "invalid utf-8 in credentials")?;
+    let mut parts = plain.splitn(2, ':');
+    let user = parts.next().map(String::from);
+    let password = parts.next().map(String::from);
+    Ok((user, password))
+}
+
+/// Query a `docker-credential-<helper>` binary for a registry's credentials.
+fn credentials_from_helper(
+    helper: &str,
+    index: &str,
+) -> Result<(Option<String>, Option<String>)> {
+    let binary = format!("docker-credential-{}", helper);
+    let mut child = Command::new(&binary)
+        .arg("get")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .chain_err(|| format!("failed to spawn credential helper {}", binary))?;
+
+    child
+        .stdin
+        .as_mut()
+        .ok_or_else(|| Error::from("credential helper stdin unavailable"))?
+        .write_all(index.as_bytes())?;
+
+    let output = child
+        .wait_with_output()
+        .chain_err(|| format!("credential helper {} failed", binary))?;
+    if !output.status.success() {
+        // A helper exits non-zero when it simply has no stored entry for
+        // this registry (e.g. "credentials not found in native keychain").
+        // That is not an error: fall through so anonymous pulls still work.
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if stderr.to_lowercase().contains("not found") {
+            return Ok((None, None));
+        }
+        bail!(
+            "credential helper {} exited with {}: {}",
+            binary,
+            output.status,
+            stderr.trim()
+        );
+    }
+
+    // Some helpers signal a missing entry with a zero exit and empty body.
+    if output.stdout.is_empty() {
+        return Ok((None, None));
+    }
+
+    let reply: HelperReply = serde_json::from_slice(&output.stdout)?;
+    let user = if reply.username.is_empty() {
+        None
+    } else {
+        Some(reply.username)
+    };
+    let secret = if reply.secret.is_empty() {
+        None
+    } else {
+        Some(reply.secret)
+    };
+    Ok((user, secret))
+}
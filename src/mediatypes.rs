@@ -0,0 +1,104 @@
+//! Media-types for API objects.
+
+use errors::*;
+use mime::{self, Mime};
+use std::fmt;
+use std::str::FromStr;
+
+/// Media-types for registry objects.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum MediaTypes {
+    /// Manifest, version 2 schema 1.
+    ManifestV2S1,
+    /// Signed manifest, version 2 schema 1.
+    ManifestV2S1Signed,
+    /// Manifest, version 2 schema 2.
+    ManifestV2S2,
+    /// Manifest List (a.k.a. "fat manifest"), version 2 schema 2.
+    ManifestList,
+    /// OCI image index, version 1.
+    OCIImageIndexV1,
+    /// Image layer, as a gzipped tarball.
+    ApplicationDockerTarGzip,
+    /// Container image configuration, version 1.
+    ContainerConfigV1,
+    /// OCI image configuration, version 1.
+    OCIImageConfigV1,
+    /// Generic JSON object.
+    ApplicationJson,
+}
+
+impl FromStr for MediaTypes {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self> {
+        let raw: Mime = s.parse().chain_err(|| format!("invalid media-type {}", s))?;
+        MediaTypes::from_mime(&raw)
+    }
+}
+
+impl MediaTypes {
+    /// Map a parsed MIME object to a known media-type.
+    pub fn from_mime(mtype: &Mime) -> Result<Self> {
+        match (mtype.type_(), mtype.subtype().as_ref(), mtype.suffix()) {
+            (mime::APPLICATION, "vnd.docker.distribution.manifest.v1", Some(suffix))
+                if suffix == "prettyjws" =>
+            {
+                Ok(MediaTypes::ManifestV2S1Signed)
+            }
+            (mime::APPLICATION, "vnd.docker.distribution.manifest.v1", _) => {
+                Ok(MediaTypes::ManifestV2S1)
+            }
+            (mime::APPLICATION, "vnd.docker.distribution.manifest.v2", _) => {
+                Ok(MediaTypes::ManifestV2S2)
+            }
+            (mime::APPLICATION, "vnd.docker.distribution.manifest.list.v2", _) => {
+                Ok(MediaTypes::ManifestList)
+            }
+            (mime::APPLICATION, "vnd.oci.image.index.v1", _) => Ok(MediaTypes::OCIImageIndexV1),
+            (mime::APPLICATION, "vnd.docker.image.rootfs.diff.tar.gzip", _) => {
+                Ok(MediaTypes::ApplicationDockerTarGzip)
+            }
+            (mime::APPLICATION, "vnd.docker.container.image.v1", _) => {
+                Ok(MediaTypes::ContainerConfigV1)
+            }
+            (mime::APPLICATION, "vnd.oci.image.config.v1", _) => Ok(MediaTypes::OCIImageConfigV1),
+            (mime::APPLICATION, "json", _) => Ok(MediaTypes::ApplicationJson),
+            _ => bail!("unknown media-type {}", mtype),
+        }
+    }
+
+    /// Return the matching MIME object for this media-type.
+    pub fn to_mime(&self) -> Mime {
+        match *self {
+            MediaTypes::ApplicationJson => mime::APPLICATION_JSON,
+            _ => self
+                .to_string()
+                .parse()
+                .expect("static media-type failed to parse"),
+        }
+    }
+}
+
+impl fmt::Display for MediaTypes {
+    /// Render the canonical string for this media-type.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match *self {
+            MediaTypes::ManifestV2S1 => "application/vnd.docker.distribution.manifest.v1+json",
+            MediaTypes::ManifestV2S1Signed => {
+                "application/vnd.docker.distribution.manifest.v1+prettyjws"
+            }
+            MediaTypes::ManifestV2S2 => "application/vnd.docker.distribution.manifest.v2+json",
+            MediaTypes::ManifestList => {
+                "application/vnd.docker.distribution.manifest.list.v2+json"
+            }
+            MediaTypes::OCIImageIndexV1 => "application/vnd.oci.image.index.v1+json",
+            MediaTypes::ApplicationDockerTarGzip => {
+                "application/vnd.docker.image.rootfs.diff.tar.gzip"
+            }
+            MediaTypes::ContainerConfigV1 => "application/vnd.docker.container.image.v1+json",
+            MediaTypes::OCIImageConfigV1 => "application/vnd.oci.image.config.v1+json",
+            MediaTypes::ApplicationJson => "application/json",
+        };
+        f.write_str(s)
+    }
+}
@@ -0,0 +1,35 @@
+//! Render a docker image.
+//!
+//! This module provides helpers to unpack the layers of an image into a
+//! local filesystem path, applying them in order.
+
+use errors::*;
+use flate2::read::GzDecoder;
+use std::{fs, path};
+use tar;
+
+/// Unpack an ordered list of gzipped layer blobs into `path`.
+///
+/// Layers are applied from the base up, so `layers` is expected to be
+/// ordered from the topmost layer to the bottom one (as returned by the
+/// manifest helpers) and is consumed in reverse.
+pub fn unpack(layers: &[Vec<u8>], target_dir: &path::Path) -> Result<()> {
+    for l in layers.iter().rev() {
+        let gz = GzDecoder::new(l.as_slice());
+        let mut archive = tar::Archive::new(gz);
+        archive.set_preserve_permissions(true);
+        archive.set_unpack_xattrs(true);
+        archive
+            .unpack(target_dir)
+            .chain_err(|| format!("failed to unpack layer to {}", target_dir.display()))?;
+    }
+    Ok(())
+}
+
+/// Create `target_dir` if it does not already exist.
+pub fn ensure_dir(target_dir: &path::Path) -> Result<()> {
+    if !target_dir.exists() {
+        fs::create_dir_all(target_dir)?;
+    }
+    Ok(())
+}
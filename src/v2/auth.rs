@@ -0,0 +1,126 @@
+//! Token-based authentication flow.
+
+use super::{BoxFuture, Client};
+use errors::*;
+use futures::prelude::*;
+use futures::future;
+use reqwest::{header, Method, StatusCode};
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    #[serde(alias = "access_token")]
+    token: String,
+}
+
+/// A parsed `WWW-Authenticate: Bearer` challenge.
+#[derive(Debug, Default)]
+struct BearerChallenge {
+    realm: String,
+    service: Option<String>,
+    scope: Option<String>,
+}
+
+impl Client {
+    /// Authenticate against the registry for the given scopes.
+    ///
+    /// This performs the distribution token dance: probe `/v2/`, parse the
+    /// returned `WWW-Authenticate: Bearer` challenge, request a token from
+    /// the advertised realm, and return a `Client` carrying that token.
+    pub fn authenticate(self, scopes: &[&str]) -> BoxFuture<Self> {
+        let scopes: Vec<String> = scopes.iter().map(|s| s.to_string()).collect();
+        let client = self.clone();
+
+        let url = format!("{}/v2/", self.base_url);
+        let req = match self.build_request(Method::GET, &url) {
+            Ok(r) => r,
+            Err(e) => return Box::new(future::err(e)),
+        };
+
+        let fut = req
+            .send()
+            .map_err(Error::from)
+            .and_then(|r| match r.status() {
+                StatusCode::UNAUTHORIZED => {
+                    let hv = r
+                        .headers()
+                        .get(header::WWW_AUTHENTICATE)
+                        .ok_or_else(|| Error::from("missing WWW-Authenticate header"))?;
+                    parse_challenge(hv.to_str()?)
+                }
+                // The registry does not require authentication.
+                StatusCode::OK => Err(Error::from("registry requires no authentication")),
+                s => Err(format!("authenticate: unexpected status {}", s).into()),
+            })
+            .and_then(move |challenge| client.request_token(&challenge, &scopes));
+
+        Box::new(fut)
+    }
+
+    /// Request a bearer token from a parsed challenge.
+    fn request_token(self, challenge: &BearerChallenge, scopes: &[String]) -> BoxFuture<Self> {
+        let mut params: Vec<(&str, String)> = Vec::new();
+        if let Some(ref svc) = challenge.service {
+            params.push(("service", svc.clone()));
+        }
+        for scope in scopes {
+            params.push(("scope", scope.clone()));
+        }
+
+        let req = match self.build_request(Method::GET, &challenge.realm) {
+            Ok(mut r) => {
+                r = r.query(&params);
+                if let Some((ref user, ref password)) = self.credentials {
+                    r = r.basic_auth(user.clone(), Some(password.clone()));
+                }
+                r
+            }
+            Err(e) => return Box::new(future::err(e)),
+        };
+
+        let client = self.clone();
+        let fut = req
+            .send()
+            .map_err(Error::from)
+            .and_then(|r| match r.status() {
+                StatusCode::OK => Ok(r),
+                s => Err(format!("request_token: unexpected status {}", s).into()),
+            })
+            .and_then(|r| r.into_body().concat2().map_err(Error::from))
+            .and_then(move |chunk| {
+                let token: TokenResponse = Client::parse_json(&chunk)?;
+                Ok(client.set_token(Some(token.token)))
+            });
+
+        Box::new(fut)
+    }
+
+    /// Store a bearer token on this client.
+    fn set_token(mut self, token: Option<String>) -> Self {
+        self.token = token;
+        self
+    }
+}
+
+/// Parse a `Bearer` authentication challenge into its components.
+fn parse_challenge(value: &str) -> Result<BearerChallenge> {
+    let value = value.trim();
+    if !value.starts_with("Bearer ") {
+        bail!("unsupported authentication scheme");
+    }
+    let rest = value.trim_start_matches("Bearer ");
+
+    let mut challenge = BearerChallenge::default();
+    for param in rest.split(',') {
+        let mut kv = param.splitn(2, '=');
+        let key = kv.next().unwrap_or("").trim();
+        let val = kv.next().unwrap_or("").trim().trim_matches('"').to_string();
+        match key {
+            "realm" => challenge.realm = val,
+            "service" => challenge.service = Some(val),
+            "scope" => challenge.scope = Some(val),
+            _ => {}
+        }
+    }
+    ensure!(!challenge.realm.is_empty(), "challenge missing realm");
+    Ok(challenge)
+}
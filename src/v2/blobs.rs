@@ -0,0 +1,211 @@
+//! Fetching of blobs (image layers and configs).
+
+use super::{BoxFuture, Client};
+use errors::*;
+use futures::prelude::*;
+use futures::{future, stream};
+use reqwest::{header, Method, StatusCode};
+
+/// Size, in bytes, of each `PATCH` chunk used by `push_chunked_blob`.
+const CHUNK_SIZE: usize = 5 * 1024 * 1024;
+
+impl Client {
+    /// Fetch a blob by its digest reference.
+    pub fn get_blob(&self, name: &str, digest: &str) -> BoxFuture<Vec<u8>> {
+        let url = format!("{}/v2/{}/blobs/{}", self.base_url, name, digest);
+        let req = match self.build_request(Method::GET, &url) {
+            Ok(r) => r,
+            Err(e) => return Box::new(futures::future::err(e)),
+        };
+
+        let fut = req
+            .send()
+            .map_err(Error::from)
+            .and_then(|r| match r.status() {
+                StatusCode::OK => Ok(r),
+                s => Err(format!("get_blob: unexpected status {}", s).into()),
+            })
+            .and_then(|r| r.into_body().concat2().map_err(Error::from))
+            .map(|chunk| chunk.to_vec());
+
+        Box::new(fut)
+    }
+
+    /// Fetch a blob and verify its content digest.
+    ///
+    /// The `digest` is both the blob reference and the expected hash; the
+    /// received body is checked against it and a
+    /// `ContentDigestMismatch` error is returned on mismatch. Verification
+    /// is skipped when the client was configured with
+    /// `accept_invalid_digests(true)`.
+    pub fn get_verified_blob(&self, name: &str, digest: &str) -> BoxFuture<Vec<u8>> {
+        // When digest verification is opted out, skip parsing entirely so
+        // callers talking to non-canonical registries never hit an
+        // unsupported-algorithm error.
+        if !self.verify_digests {
+            return self.get_blob(name, digest);
+        }
+
+        let expected = match super::digest::ContentDigest::try_new(digest) {
+            Ok(d) => d,
+            Err(e) => return Box::new(future::err(e)),
+        };
+
+        let fut = self.get_blob(name, digest).and_then(move |body| {
+            expected.verify(&body)?;
+            Ok(body)
+        });
+
+        Box::new(fut)
+    }
+
+    /// Check whether a blob is already present in the repository.
+    ///
+    /// Callers can use this to skip uploading layers the registry already
+    /// holds.
+    pub fn has_blob(&self, name: &str, digest: &str) -> BoxFuture<bool> {
+        let url = format!("{}/v2/{}/blobs/{}", self.base_url, name, digest);
+        let req = match self.build_request(Method::HEAD, &url) {
+            Ok(r) => r,
+            Err(e) => return Box::new(future::err(e)),
+        };
+
+        let fut = req.send().map_err(Error::from).and_then(|r| match r.status() {
+            StatusCode::OK => Ok(true),
+            StatusCode::NOT_FOUND => Ok(false),
+            s => Err(format!("has_blob: unexpected status {}", s).into()),
+        });
+
+        Box::new(fut)
+    }
+
+    /// Upload a blob monolithically.
+    ///
+    /// This opens an upload session with `POST .../blobs/uploads/` and
+    /// finalizes it with a single `PUT .../?digest=<digest>` carrying the
+    /// whole body. Returns the canonical digest accepted by the registry.
+    pub fn push_blob(&self, name: &str, body: &[u8], digest: &str) -> BoxFuture<String> {
+        let body = body.to_vec();
+        let digest = digest.to_string();
+        let client = self.clone();
+
+        let fut = self.start_upload(name).and_then(move |location| {
+            let url = append_digest(&location, &digest);
+            let req = match client.build_request(Method::PUT, &url) {
+                Ok(r) => r
+                    .header(header::CONTENT_TYPE, "application/octet-stream")
+                    .body(body),
+                Err(e) => return future::Either::A(future::err(e)),
+            };
+            let fut = req
+                .send()
+                .map_err(Error::from)
+                .and_then(|r| finalize_upload(r, digest));
+            future::Either::B(fut)
+        });
+
+        Box::new(fut)
+    }
+
+    /// Upload a blob in chunks.
+    ///
+    /// A session is opened with `POST`, each chunk is streamed with a
+    /// `PATCH` carrying a `Content-Range`, and the upload is finalized with
+    /// an empty `PUT .../?digest=<digest>`.
+    pub fn push_chunked_blob(&self, name: &str, body: &[u8], digest: &str) -> BoxFuture<String> {
+        let chunks: Vec<Vec<u8>> = body.chunks(CHUNK_SIZE).map(|c| c.to_vec()).collect();
+        let digest = digest.to_string();
+        let client = self.clone();
+
+        let fut = self.start_upload(name).and_then(move |location| {
+            let mut offset = 0u64;
+            let client2 = client.clone();
+            stream::iter_ok::<_, Error>(chunks)
+                .fold(location, move |location, chunk| {
+                    let end = offset + chunk.len() as u64;
+                    let range = format!("{}-{}", offset, end.saturating_sub(1));
+                    offset = end;
+                    let req = client2
+                        .build_request(Method::PATCH, &location)
+                        .map(|r| {
+                            r.header(header::CONTENT_TYPE, "application/octet-stream")
+                                .header(header::CONTENT_RANGE, range)
+                                .body(chunk)
+                        });
+                    let req = match req {
+                        Ok(r) => r,
+                        Err(e) => return future::Either::A(future::err(e)),
+                    };
+                    let fut = req.send().map_err(Error::from).and_then(|r| {
+                        if !r.status().is_success() {
+                            return Err(format!("push_chunked_blob: unexpected status {}", r.status()).into());
+                        }
+                        next_location(&r)
+                    });
+                    future::Either::B(fut)
+                })
+                .and_then(move |location| {
+                    let url = append_digest(&location, &digest);
+                    let req = match client.build_request(Method::PUT, &url) {
+                        Ok(r) => r,
+                        Err(e) => return future::Either::A(future::err(e)),
+                    };
+                    let fut = req
+                        .send()
+                        .map_err(Error::from)
+                        .and_then(|r| finalize_upload(r, digest));
+                    future::Either::B(fut)
+                })
+        });
+
+        Box::new(fut)
+    }
+
+    /// Open a blob-upload session, returning its `Location`.
+    fn start_upload(&self, name: &str) -> BoxFuture<String> {
+        let url = format!("{}/v2/{}/blobs/uploads/", self.base_url, name);
+        let req = match self.build_request(Method::POST, &url) {
+            Ok(r) => r,
+            Err(e) => return Box::new(future::err(e)),
+        };
+
+        let fut = req.send().map_err(Error::from).and_then(|r| match r.status() {
+            StatusCode::ACCEPTED => next_location(&r),
+            s => Err(format!("start_upload: unexpected status {}", s).into()),
+        });
+
+        Box::new(fut)
+    }
+}
+
+/// Extract the upload-session `Location` from a response.
+fn next_location(resp: &::reqwest::r#async::Response) -> Result<String> {
+    resp.headers()
+        .get(header::LOCATION)
+        .ok_or_else(|| Error::from("upload response missing Location header"))?
+        .to_str()
+        .map(String::from)
+        .map_err(Error::from)
+}
+
+/// Append a `digest` query parameter to an upload location.
+fn append_digest(location: &str, digest: &str) -> String {
+    let sep = if location.contains('?') { '&' } else { '?' };
+    format!("{}{}digest={}", location, sep, digest)
+}
+
+/// Validate a finalizing `PUT` and surface the stored digest.
+fn finalize_upload(resp: ::reqwest::r#async::Response, fallback: String) -> Result<String> {
+    match resp.status() {
+        StatusCode::CREATED => {
+            let stored = resp
+                .headers()
+                .get("Docker-Content-Digest")
+                .and_then(|hv| hv.to_str().ok())
+                .map(String::from)
+                .unwrap_or(fallback);
+            Ok(stored)
+        }
+        s => Err(format!("blob upload finalize: unexpected status {}", s).into()),
+    }
+}
@@ -0,0 +1,154 @@
+//! Configuration for a `v2::Client`.
+
+use super::Client;
+use errors::*;
+use reqwest::r#async;
+use std::fmt;
+use std::sync::Arc;
+
+/// A user-supplied credential resolver, keyed on the registry index.
+pub type CredentialResolver =
+    dyn Fn(&str) -> Result<(Option<String>, Option<String>)> + Send + Sync;
+
+/// Builder for a `Client`.
+pub struct Config {
+    index: String,
+    insecure_registry: bool,
+    user_agent: Option<String>,
+    username: Option<String>,
+    password: Option<String>,
+    verify_digests: bool,
+    credential_resolver: Option<Arc<CredentialResolver>>,
+}
+
+impl fmt::Debug for Config {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Config")
+            .field("index", &self.index)
+            .field("insecure_registry", &self.insecure_registry)
+            .field("user_agent", &self.user_agent)
+            .field("username", &self.username)
+            .field("verify_digests", &self.verify_digests)
+            .field(
+                "credential_resolver",
+                &self.credential_resolver.as_ref().map(|_| "<fn>"),
+            )
+            .finish()
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            index: "registry-1.docker.io".into(),
+            insecure_registry: false,
+            user_agent: Some(::USER_AGENT.to_string()),
+            username: None,
+            password: None,
+            verify_digests: true,
+            credential_resolver: None,
+        }
+    }
+}
+
+impl Config {
+    /// Set registry service to use (vhost or IP).
+    pub fn registry(mut self, reg: &str) -> Self {
+        self.index = reg.to_owned();
+        self
+    }
+
+    /// Whether to use an insecure HTTP connection to the registry.
+    pub fn insecure_registry(mut self, insecure: bool) -> Self {
+        self.insecure_registry = insecure;
+        self
+    }
+
+    /// Set the user-agent to be used for registry requests.
+    pub fn user_agent(mut self, user_agent: Option<String>) -> Self {
+        self.user_agent = user_agent;
+        self
+    }
+
+    /// Set the username to use for authentication.
+    pub fn username(mut self, user: Option<String>) -> Self {
+        self.username = user;
+        self
+    }
+
+    /// Set the password to use for authentication.
+    pub fn password(mut self, password: Option<String>) -> Self {
+        self.password = password;
+        self
+    }
+
+    /// Whether to skip content-digest verification of downloaded blobs and
+    /// manifests.
+    ///
+    /// Verification is on by default; pass `true` to disable it for
+    /// registries that do not return canonical digests.
+    pub fn accept_invalid_digests(mut self, accept: bool) -> Self {
+        self.verify_digests = !accept;
+        self
+    }
+
+    /// Supply a custom credential-resolution callback.
+    ///
+    /// The callback is invoked at `build()` time with the registry index
+    /// whenever no explicit username/password have been set, allowing
+    /// library users to plug in their own credential store.
+    pub fn credential_resolver<F>(mut self, resolver: F) -> Self
+    where
+        F: Fn(&str) -> Result<(Option<String>, Option<String>)> + Send + Sync + 'static,
+    {
+        self.credential_resolver = Some(Arc::new(resolver));
+        self
+    }
+
+    /// Read credentials from a JSON object.
+    pub fn read_credentials<T: ::std::io::Read>(mut self, reader: T) -> Self {
+        if let Ok(creds) = ::get_credentials(reader, &self.index) {
+            self.username = creds.0;
+            self.password = creds.1;
+        }
+        self
+    }
+
+    /// Return a `Client` to interact with a v2 registry.
+    pub fn build(self) -> Result<Client> {
+        let base = if self.insecure_registry {
+            format!("http://{}", self.index)
+        } else {
+            format!("https://{}", self.index)
+        };
+        trace!("Built client for {}", base);
+
+        let (mut username, mut password) = (self.username, self.password);
+        if username.is_none() && password.is_none() {
+            if let Some(ref resolver) = self.credential_resolver {
+                let (u, p) = resolver(&self.index)?;
+                username = u;
+                password = p;
+            }
+        }
+
+        let creds = match (username, password) {
+            (Some(u), Some(p)) => Some((u, p)),
+            (None, None) => None,
+            _ => bail!("username and password must be set together"),
+        };
+
+        let client = r#async::Client::builder()
+            .build()
+            .chain_err(|| "failed to build HTTP client")?;
+
+        Ok(Client {
+            base_url: base,
+            credentials: creds,
+            user_agent: self.user_agent,
+            token: None,
+            verify_digests: self.verify_digests,
+            client,
+        })
+    }
+}
@@ -0,0 +1,86 @@
+//! Parsing and verification of content digests.
+//!
+//! Digests are of the form `<algorithm>:<hex>`, e.g.
+//! `sha256:deadbeef...`, as defined by the distribution spec.
+
+use errors::*;
+use sha2::{Digest, Sha256, Sha512};
+use std::fmt;
+
+/// A digest algorithm understood by the client.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DigestAlgorithm {
+    Sha256,
+    Sha512,
+}
+
+impl DigestAlgorithm {
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "sha256" => Ok(DigestAlgorithm::Sha256),
+            "sha512" => Ok(DigestAlgorithm::Sha512),
+            other => bail!("unsupported digest algorithm {}", other),
+        }
+    }
+}
+
+/// A parsed content digest.
+#[derive(Clone, Debug)]
+pub struct ContentDigest {
+    algorithm: DigestAlgorithm,
+    hex: String,
+}
+
+impl ContentDigest {
+    /// Parse a `<algorithm>:<hex>` digest reference.
+    pub fn try_new(digest: &str) -> Result<Self> {
+        let mut parts = digest.splitn(2, ':');
+        let algorithm = DigestAlgorithm::from_str(parts.next().unwrap_or(""))?;
+        let hex = parts
+            .next()
+            .ok_or_else(|| Error::from(format!("malformed digest {}", digest)))?;
+        ensure!(!hex.is_empty(), "empty digest value");
+        Ok(Self {
+            algorithm,
+            hex: hex.to_ascii_lowercase(),
+        })
+    }
+
+    /// Verify that `body` hashes to this digest.
+    pub fn verify(&self, body: &[u8]) -> Result<()> {
+        let found = match self.algorithm {
+            DigestAlgorithm::Sha256 => hex_encode(Sha256::digest(body).as_slice()),
+            DigestAlgorithm::Sha512 => hex_encode(Sha512::digest(body).as_slice()),
+        };
+        if found == self.hex {
+            Ok(())
+        } else {
+            Err(ErrorKind::ContentDigestMismatch(self.to_string(), self.format(&found)).into())
+        }
+    }
+
+    /// Render `hex` with this digest's algorithm prefix.
+    fn format(&self, hex: &str) -> String {
+        let prefix = match self.algorithm {
+            DigestAlgorithm::Sha256 => "sha256",
+            DigestAlgorithm::Sha512 => "sha512",
+        };
+        format!("{}:{}", prefix, hex)
+    }
+}
+
+impl fmt::Display for ContentDigest {
+    /// Render this digest back into its canonical string form.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.format(&self.hex))
+    }
+}
+
+/// Lower-case hex encoding of a byte slice.
+fn hex_encode(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len() * 2);
+    for b in bytes {
+        out.push_str(&format!("{:02x}", b));
+    }
+    out
+}
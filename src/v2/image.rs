@@ -0,0 +1,82 @@
+//! The image configuration blob.
+//!
+//! A schema-2 manifest points at a configuration blob
+//! (`application/vnd.docker.container.image.v1+json` or its OCI
+//! counterpart) describing the image's platform, root filesystem and
+//! runtime defaults.
+
+use super::{BoxFuture, Client};
+use errors::*;
+use futures::prelude::*;
+use std::collections::HashMap;
+
+/// A parsed image configuration blob.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct ImageConfig {
+    #[serde(default)]
+    pub architecture: String,
+    #[serde(default)]
+    pub os: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created: Option<String>,
+    #[serde(default)]
+    pub rootfs: RootFs,
+    #[serde(default)]
+    pub history: Vec<History>,
+    #[serde(default)]
+    pub config: RuntimeConfig,
+}
+
+/// The image's root filesystem descriptor.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct RootFs {
+    #[serde(rename = "type", default)]
+    pub fs_type: String,
+    #[serde(default)]
+    pub diff_ids: Vec<String>,
+}
+
+/// A single entry in the image build history.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct History {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_by: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+    #[serde(default)]
+    pub empty_layer: bool,
+}
+
+/// Runtime defaults embedded in the image configuration.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct RuntimeConfig {
+    #[serde(rename = "Env", default)]
+    pub env: Vec<String>,
+    #[serde(rename = "Entrypoint", default, skip_serializing_if = "Option::is_none")]
+    pub entrypoint: Option<Vec<String>>,
+    #[serde(rename = "Cmd", default, skip_serializing_if = "Option::is_none")]
+    pub cmd: Option<Vec<String>>,
+    #[serde(rename = "ExposedPorts", default)]
+    pub exposed_ports: HashMap<String, EmptyObject>,
+    #[serde(rename = "Labels", default)]
+    pub labels: HashMap<String, String>,
+    #[serde(rename = "WorkingDir", default, skip_serializing_if = "Option::is_none")]
+    pub working_dir: Option<String>,
+}
+
+/// The empty JSON object used as a set value (e.g. for exposed ports).
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct EmptyObject {}
+
+impl Client {
+    /// Fetch and deserialize the image configuration blob.
+    pub fn get_config_blob(&self, name: &str, digest: &str) -> BoxFuture<ImageConfig> {
+        let fut = self.get_verified_blob(name, digest).and_then(|body| {
+            let config: ImageConfig = Client::parse_json(&body)?;
+            Ok(config)
+        });
+        Box::new(fut)
+    }
+}
@@ -0,0 +1,288 @@
+//! Manifest types and retrieval.
+
+use super::{BoxFuture, Client};
+use errors::*;
+use futures::prelude::*;
+use futures::future;
+use mediatypes::MediaTypes;
+use reqwest::{header, Method, StatusCode};
+use std::str::FromStr;
+
+/// Manifest version 2 schema 1, signed.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct ManifestSchema1Signed {
+    #[serde(rename = "schemaVersion")]
+    schema_version: u16,
+    name: String,
+    tag: String,
+    architecture: String,
+    #[serde(rename = "fsLayers")]
+    fs_layers: Vec<S1Layer>,
+    history: Vec<S1History>,
+    signatures: Vec<S1Signature>,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+struct S1Layer {
+    #[serde(rename = "blobSum")]
+    blob_sum: String,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+struct S1History {
+    #[serde(rename = "v1Compatibility")]
+    v1_compatibility: String,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+struct S1Signature {
+    header: ::serde_json::Value,
+    signature: String,
+    protected: String,
+}
+
+impl ManifestSchema1Signed {
+    /// Return the ordered list of layer digests, topmost first.
+    pub fn get_layers(&self) -> Vec<String> {
+        self.fs_layers
+            .iter()
+            .map(|l| l.blob_sum.clone())
+            .collect()
+    }
+}
+
+/// Manifest version 2 schema 2.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct ManifestSchema2 {
+    #[serde(rename = "schemaVersion")]
+    schema_version: u16,
+    #[serde(rename = "mediaType")]
+    media_type: String,
+    config: Descriptor,
+    layers: Vec<Descriptor>,
+}
+
+impl ManifestSchema2 {
+    /// Return the ordered list of layer digests, topmost first.
+    pub fn get_layers(&self) -> Vec<String> {
+        self.layers
+            .iter()
+            .rev()
+            .map(|l| l.digest.clone())
+            .collect()
+    }
+
+    /// Return the digest of the image configuration blob.
+    pub fn config_digest(&self) -> String {
+        self.config.digest.clone()
+    }
+}
+
+/// A content descriptor, as used by schema-2 manifests and indexes.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct Descriptor {
+    #[serde(rename = "mediaType")]
+    pub media_type: String,
+    pub digest: String,
+    pub size: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub platform: Option<Platform>,
+}
+
+/// Platform constraints attached to a manifest-list entry.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct Platform {
+    pub architecture: String,
+    pub os: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub variant: Option<String>,
+}
+
+/// A manifest list (or OCI image index): a set of per-platform manifests.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct ManifestList {
+    #[serde(rename = "schemaVersion")]
+    schema_version: u16,
+    #[serde(rename = "mediaType", default)]
+    media_type: String,
+    pub manifests: Vec<Descriptor>,
+}
+
+impl ManifestList {
+    /// Select the descriptor matching the requested platform.
+    ///
+    /// An exact `architecture`/`os` match is preferred; when none is
+    /// present the first descriptor lacking platform information is used
+    /// as a fallback, so callers still get a descriptor back for lists
+    /// that omit platform metadata.
+    pub fn find_platform(&self, architecture: &str, os: &str) -> Option<&Descriptor> {
+        self.manifests
+            .iter()
+            .find(|d| match d.platform {
+                Some(ref p) => p.architecture == architecture && p.os == os,
+                None => false,
+            })
+            .or_else(|| self.manifests.iter().find(|d| d.platform.is_none()))
+    }
+}
+
+impl Client {
+    /// Fetch an image manifest, returning its raw body.
+    ///
+    /// When `reference` is itself a content digest, the received body is
+    /// verified against it unless the client was configured with
+    /// `accept_invalid_digests(true)`.
+    pub fn get_manifest(&self, name: &str, reference: &str) -> BoxFuture<Vec<u8>> {
+        let url = format!("{}/v2/{}/manifests/{}", self.base_url, name, reference);
+        let req = match self.build_request(Method::GET, &url) {
+            Ok(r) => r.header(header::ACCEPT, accept_headers()),
+            Err(e) => return Box::new(future::err(e)),
+        };
+
+        // Only a digest reference lets us verify the body; a tag cannot.
+        let expected = if self.verify_digests {
+            super::digest::ContentDigest::try_new(reference).ok()
+        } else {
+            None
+        };
+
+        let fut = req
+            .send()
+            .map_err(Error::from)
+            .and_then(|r| match r.status() {
+                StatusCode::OK => Ok(r),
+                s => Err(format!("get_manifest: unexpected status {}", s).into()),
+            })
+            .and_then(|r| r.into_body().concat2().map_err(Error::from))
+            .and_then(move |chunk| {
+                let body = chunk.to_vec();
+                if let Some(ref d) = expected {
+                    d.verify(&body)?;
+                }
+                Ok(body)
+            });
+
+        Box::new(fut)
+    }
+
+    /// Check whether a manifest exists, returning its media-type.
+    ///
+    /// The returned media-type also covers manifest lists and OCI image
+    /// indexes, so callers can branch on a multi-arch top-level object.
+    pub fn has_manifest(
+        &self,
+        name: &str,
+        reference: &str,
+        mediatypes: Option<&[&str]>,
+    ) -> BoxFuture<Option<MediaTypes>> {
+        let accept = match mediatypes {
+            Some(list) => list.join(", "),
+            None => accept_headers(),
+        };
+        let url = format!("{}/v2/{}/manifests/{}", self.base_url, name, reference);
+        let req = match self.build_request(Method::HEAD, &url) {
+            Ok(r) => r.header(header::ACCEPT, accept),
+            Err(e) => return Box::new(future::err(e)),
+        };
+
+        let fut = req.send().map_err(Error::from).and_then(|r| {
+            let ct = r
+                .headers()
+                .get(header::CONTENT_TYPE)
+                .and_then(|hv| hv.to_str().ok())
+                .map(String::from);
+            match (r.status(), ct) {
+                (StatusCode::OK, Some(ref ct)) => Ok(Some(MediaTypes::from_str(ct)?)),
+                (StatusCode::NOT_FOUND, _) => Ok(None),
+                (s, _) => Err(format!("has_manifest: unexpected status {}", s).into()),
+            }
+        });
+
+        Box::new(fut)
+    }
+
+    /// Upload a manifest under `reference` (a tag or digest).
+    ///
+    /// The manifest is sent with the given `media_type` as its
+    /// `Content-Type`; the registry's `Docker-Content-Digest` response
+    /// header is returned to the caller.
+    pub fn put_manifest(
+        &self,
+        name: &str,
+        reference: &str,
+        media_type: &MediaTypes,
+        body: Vec<u8>,
+    ) -> BoxFuture<String> {
+        let url = format!("{}/v2/{}/manifests/{}", self.base_url, name, reference);
+        let req = match self.build_request(Method::PUT, &url) {
+            Ok(r) => r.header(header::CONTENT_TYPE, media_type.to_string()).body(body),
+            Err(e) => return Box::new(future::err(e)),
+        };
+
+        let fut = req.send().map_err(Error::from).and_then(|r| match r.status() {
+            StatusCode::CREATED => r
+                .headers()
+                .get("Docker-Content-Digest")
+                .ok_or_else(|| Error::from("put_manifest: missing Docker-Content-Digest header"))?
+                .to_str()
+                .map(String::from)
+                .map_err(Error::from),
+            s => Err(format!("put_manifest: unexpected status {}", s).into()),
+        });
+
+        Box::new(fut)
+    }
+
+    /// Resolve a multi-arch image to a single per-platform manifest.
+    ///
+    /// This fetches the top-level manifest list (or OCI image index) at
+    /// `reference`, selects the descriptor whose platform matches
+    /// `architecture`/`os` (falling back to a descriptor that carries no
+    /// platform metadata when no entry matches exactly), and re-fetches the
+    /// per-arch manifest by its digest.
+    pub fn get_manifest_for_platform(
+        &self,
+        name: &str,
+        reference: &str,
+        architecture: &str,
+        os: &str,
+    ) -> BoxFuture<Vec<u8>> {
+        let client = self.clone();
+        let name = name.to_string();
+        let architecture = architecture.to_string();
+        let os = os.to_string();
+
+        let fut = self
+            .get_manifest(&name, reference)
+            .and_then(move |body| {
+                let list: ManifestList = Client::parse_json(&body)?;
+                let digest = list
+                    .find_platform(&architecture, &os)
+                    .map(|d| d.digest.clone())
+                    .ok_or_else(|| {
+                        Error::from(format!(
+                            "no manifest for platform {}/{}",
+                            architecture, os
+                        ))
+                    })?;
+                Ok((client, name, digest))
+            })
+            .and_then(|(client, name, digest)| client.get_manifest(&name, &digest));
+
+        Box::new(fut)
+    }
+}
+
+/// Accept header listing every manifest media-type we understand.
+fn accept_headers() -> String {
+    [
+        MediaTypes::ManifestV2S2,
+        MediaTypes::ManifestV2S1Signed,
+        MediaTypes::ManifestList,
+        MediaTypes::OCIImageIndexV1,
+    ]
+    .iter()
+    .map(MediaTypes::to_string)
+    .collect::<Vec<_>>()
+    .join(", ")
+}
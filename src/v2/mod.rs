@@ -0,0 +1,98 @@
+//! Client library for Docker Registry API v2.
+//!
+//! This module provides a `Client` to interact with a registry
+//! implementing the [distribution specification][spec].
+//!
+//! [spec]: https://docs.docker.com/registry/spec/api/
+
+use errors::*;
+use futures::prelude::*;
+use futures::future;
+use reqwest::{header, r#async, Method, StatusCode, Url};
+use serde_json;
+
+mod config;
+pub use self::config::Config;
+
+mod auth;
+pub mod blobs;
+pub mod digest;
+pub mod image;
+pub mod manifest;
+pub mod tags;
+
+/// A boxed asynchronous result, used throughout the v2 client.
+pub(crate) type BoxFuture<T> = Box<dyn Future<Item = T, Error = Error> + Send>;
+
+/// A Client to make outgoing API requests to a registry.
+#[derive(Clone, Debug)]
+pub struct Client {
+    base_url: String,
+    credentials: Option<(String, String)>,
+    user_agent: Option<String>,
+    token: Option<String>,
+    verify_digests: bool,
+    client: r#async::Client,
+}
+
+impl Client {
+    /// Return a `Config` builder for a `Client`.
+    pub fn configure() -> Config {
+        Config::default()
+    }
+
+    /// Ensure remote registry supports v2 API.
+    pub fn is_v2_supported(&self) -> impl Future<Item = bool, Error = Error> {
+        let api_header = "Docker-Distribution-API-Version";
+        let api_version = "registry/2.0";
+
+        let url = format!("{}/v2/", self.base_url);
+        let req = match self.build_request(Method::GET, &url) {
+            Ok(r) => r,
+            Err(e) => return future::Either::A(future::err(e)),
+        };
+
+        let fut = req.send().map_err(Error::from).and_then(move |r| {
+            trace!("GET '{}' status {}", r.url(), r.status());
+            let version = r
+                .headers()
+                .get(api_header)
+                .and_then(|hv| hv.to_str().ok())
+                .map(String::from);
+            match (r.status(), version) {
+                (StatusCode::OK, Some(ref v)) | (StatusCode::UNAUTHORIZED, Some(ref v))
+                    if v == api_version =>
+                {
+                    Ok(true)
+                }
+                (s, _) => {
+                    trace!("Registry returned status {}", s);
+                    Ok(false)
+                }
+            }
+        });
+        future::Either::B(fut)
+    }
+
+    /// Build a request with the proper headers and authentication token.
+    pub(crate) fn build_request(
+        &self,
+        method: Method,
+        url: &str,
+    ) -> Result<r#async::RequestBuilder> {
+        let parsed = Url::parse(url)?;
+        let mut req = self.client.request(method, parsed);
+        if let Some(ref t) = self.token {
+            req = req.header(header::AUTHORIZATION, format!("Bearer {}", t));
+        }
+        if let Some(ref ua) = self.user_agent {
+            req = req.header(header::USER_AGENT, ua.as_str());
+        }
+        Ok(req)
+    }
+
+    /// Deserialize a JSON body into `T`.
+    pub(crate) fn parse_json<T: serde::de::DeserializeOwned>(body: &[u8]) -> Result<T> {
+        serde_json::from_slice(body).map_err(Error::from)
+    }
+}
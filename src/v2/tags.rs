@@ -0,0 +1,87 @@
+//! Listing of repository tags.
+
+use super::Client;
+use errors::*;
+use futures::prelude::*;
+use futures::{future, stream};
+use reqwest::{Method, StatusCode};
+
+/// Convenience alias for a stream of tag names.
+type StreamTags = Box<dyn Stream<Item = String, Error = Error> + Send>;
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+struct Tags {
+    name: String,
+    tags: Vec<String>,
+}
+
+impl Client {
+    /// List existing tags for a repository.
+    ///
+    /// When `paginate` is set, results are fetched `n` at a time. Subsequent
+    /// pages are requested from the tag-list endpoint using the query of
+    /// the `Link` response header.
+    pub fn get_tags(&self, name: &str, paginate: Option<u32>) -> StreamTags {
+        let base_url = format!("{}/v2/{}/tags/list", self.base_url, name);
+        let url = match paginate {
+            Some(n) => format!("{}?n={}", base_url, n),
+            None => base_url.clone(),
+        };
+
+        let client = self.clone();
+        let inner = stream::unfold(Some(url), move |next| {
+            let url = match next {
+                Some(u) => u,
+                None => return None,
+            };
+
+            let client = client.clone();
+            let base_url = base_url.clone();
+            let req = match client.build_request(Method::GET, &url) {
+                Ok(r) => r,
+                Err(e) => return Some(future::Either::A(future::err(e))),
+            };
+
+            let fut = req
+                .send()
+                .map_err(Error::from)
+                .and_then(move |r| {
+                    let next = next_page(&r, &base_url);
+                    match r.status() {
+                        StatusCode::OK => Ok((r, next)),
+                        s => Err(format!("get_tags: unexpected status {}", s).into()),
+                    }
+                })
+                .and_then(|(r, next)| {
+                    r.into_body()
+                        .concat2()
+                        .map_err(Error::from)
+                        .map(move |chunk| (chunk, next))
+                })
+                .and_then(|(chunk, next)| {
+                    let tags: Tags = Client::parse_json(&chunk)?;
+                    Ok((stream::iter_ok(tags.tags), next))
+                });
+            Some(future::Either::B(fut))
+        })
+        .flatten();
+
+        Box::new(inner)
+    }
+}
+
+/// Build the next page URL from a `Link` response header, if present.
+///
+/// The query of the advertised link is applied to `base_url`; a link
+/// without a query yields no next page.
+fn next_page(resp: &::reqwest::r#async::Response, base_url: &str) -> Option<String> {
+    resp.headers()
+        .get(::reqwest::header::LINK)
+        .and_then(|hv| hv.to_str().ok())
+        .and_then(|link| {
+            let start = link.find('<')? + 1;
+            let end = link.find('>')?;
+            let query = link[start..end].splitn(2, '?').nth(1)?;
+            Some(format!("{}?{}", base_url, query))
+        })
+}
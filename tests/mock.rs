@@ -0,0 +1,6 @@
+#[macro_use]
+extern crate serde_json;
+
+mod harness;
+mod pull;
+mod tags;
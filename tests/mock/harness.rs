@@ -0,0 +1,536 @@
+//! In-process mock registry harness.
+//!
+//! This spins up a `TcpListener`-backed HTTP server implementing a
+//! realistic subset of the distribution API, so tests can exercise the
+//! full pull flow (auth challenge -> token -> manifest -> config ->
+//! layers) end to end. Images are registered by supplying a config blob
+//! plus raw layer bytes; the harness computes digests and assembles a
+//! valid schema-2 manifest.
+
+extern crate serde_json;
+extern crate sha2;
+
+use self::sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// Monotonic counter handing out unique blob-upload session ids.
+static UPLOAD_SEQ: AtomicUsize = AtomicUsize::new(0);
+
+const MANIFEST_V2S2: &str = "application/vnd.docker.distribution.manifest.v2+json";
+const MANIFEST_LIST: &str = "application/vnd.docker.distribution.manifest.list.v2+json";
+const IMAGE_CONFIG: &str = "application/vnd.docker.container.image.v1+json";
+const LAYER_GZIP: &str = "application/vnd.docker.image.rootfs.diff.tar.gzip";
+
+/// A stored object: its media-type and body.
+#[derive(Clone)]
+struct Object {
+    media_type: String,
+    body: Vec<u8>,
+}
+
+/// Mutable state shared with the serving thread.
+#[derive(Default)]
+struct State {
+    /// Blobs addressed by digest.
+    blobs: HashMap<String, Object>,
+    /// Manifests addressed by `<name>/<reference>` (tag or digest).
+    manifests: HashMap<String, Object>,
+    /// Tag lists addressed by repository name.
+    tags: HashMap<String, Vec<String>>,
+    /// In-flight blob uploads, addressed by session id.
+    uploads: HashMap<String, Vec<u8>>,
+}
+
+/// A running mock registry.
+pub struct MockRegistry {
+    address: String,
+    state: Arc<Mutex<State>>,
+}
+
+impl MockRegistry {
+    /// Start a mock registry on an ephemeral port.
+    pub fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("failed to bind mock registry");
+        let address = listener.local_addr().unwrap().to_string();
+        let state = Arc::new(Mutex::new(State::default()));
+
+        let thread_state = state.clone();
+        let thread_addr = address.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                if let Ok(stream) = stream {
+                    let state = thread_state.clone();
+                    let addr = thread_addr.clone();
+                    thread::spawn(move || handle(stream, &addr, &state));
+                }
+            }
+        });
+
+        MockRegistry { address, state }
+    }
+
+    /// Return the `host:port` this registry listens on.
+    pub fn address(&self) -> &str {
+        &self.address
+    }
+
+    /// Register a schema-2 image and return its manifest digest.
+    ///
+    /// The config blob and each layer are stored under their computed
+    /// digests, a schema-2 manifest is assembled, and it is made available
+    /// both by `reference` (a tag) and by its own digest.
+    pub fn register_image(
+        &self,
+        name: &str,
+        reference: &str,
+        config: &[u8],
+        layers: &[&[u8]],
+    ) -> String {
+        let config_digest = digest_of(config);
+        let layer_descriptors: Vec<_> = layers
+            .iter()
+            .map(|l| {
+                let d = digest_of(l);
+                json!({ "mediaType": LAYER_GZIP, "digest": d, "size": l.len() })
+            })
+            .collect();
+
+        let manifest = json!({
+            "schemaVersion": 2,
+            "mediaType": MANIFEST_V2S2,
+            "config": { "mediaType": IMAGE_CONFIG, "digest": config_digest, "size": config.len() },
+            "layers": layer_descriptors,
+        });
+        let manifest_body = serde_json::to_vec(&manifest).unwrap();
+        let manifest_digest = digest_of(&manifest_body);
+
+        let mut state = self.state.lock().unwrap();
+        state.blobs.insert(
+            config_digest,
+            Object {
+                media_type: IMAGE_CONFIG.to_string(),
+                body: config.to_vec(),
+            },
+        );
+        for l in layers {
+            state.blobs.insert(
+                digest_of(l),
+                Object {
+                    media_type: LAYER_GZIP.to_string(),
+                    body: l.to_vec(),
+                },
+            );
+        }
+        let obj = Object {
+            media_type: MANIFEST_V2S2.to_string(),
+            body: manifest_body,
+        };
+        state
+            .manifests
+            .insert(format!("{}/{}", name, reference), obj.clone());
+        state
+            .manifests
+            .insert(format!("{}/{}", name, manifest_digest), obj);
+
+        manifest_digest
+    }
+
+    /// Register a manifest list pointing at previously-registered images.
+    ///
+    /// Each `(architecture, os, digest)` triple becomes an entry in a
+    /// manifest list served under `reference` and its own digest.
+    pub fn register_manifest_list(
+        &self,
+        name: &str,
+        reference: &str,
+        entries: &[(&str, &str, &str)],
+    ) -> String {
+        let manifests: Vec<_> = entries
+            .iter()
+            .map(|(arch, os, digest)| {
+                json!({
+                    "mediaType": MANIFEST_V2S2,
+                    "digest": digest,
+                    "size": 0,
+                    "platform": { "architecture": arch, "os": os },
+                })
+            })
+            .collect();
+
+        let list = json!({
+            "schemaVersion": 2,
+            "mediaType": MANIFEST_LIST,
+            "manifests": manifests,
+        });
+        let body = serde_json::to_vec(&list).unwrap();
+        let list_digest = digest_of(&body);
+
+        let obj = Object {
+            media_type: MANIFEST_LIST.to_string(),
+            body,
+        };
+        let mut state = self.state.lock().unwrap();
+        state
+            .manifests
+            .insert(format!("{}/{}", name, reference), obj.clone());
+        state
+            .manifests
+            .insert(format!("{}/{}", name, list_digest), obj);
+
+        list_digest
+    }
+
+    /// Register the tag list served for a repository.
+    pub fn register_tags(&self, name: &str, tags: &[&str]) {
+        let tags = tags.iter().map(|t| t.to_string()).collect();
+        self.state.lock().unwrap().tags.insert(name.to_string(), tags);
+    }
+
+    /// Store a blob under an explicit digest key.
+    ///
+    /// Unlike `register_image`, the key is not derived from `body`, so
+    /// tests can serve content that does not match the digest it is fetched
+    /// under and exercise the verification path.
+    pub fn register_raw_blob(&self, digest: &str, body: &[u8]) {
+        self.state.lock().unwrap().blobs.insert(
+            digest.to_string(),
+            Object {
+                media_type: LAYER_GZIP.to_string(),
+                body: body.to_vec(),
+            },
+        );
+    }
+}
+
+/// Compute the `sha256:<hex>` digest of a byte slice.
+pub fn digest_of(body: &[u8]) -> String {
+    let hash = Sha256::digest(body);
+    let mut hex = String::with_capacity(hash.len() * 2);
+    for b in hash.as_slice() {
+        hex.push_str(&format!("{:02x}", b));
+    }
+    format!("sha256:{}", hex)
+}
+
+/// A parsed request line and headers.
+struct Request {
+    method: String,
+    path: String,
+    headers: HashMap<String, String>,
+    body: Vec<u8>,
+}
+
+/// Serve a single connection.
+fn handle(stream: TcpStream, addr: &str, state: &Arc<Mutex<State>>) {
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+    let req = match parse_request(&mut reader) {
+        Some(r) => r,
+        None => return,
+    };
+    let mut stream = stream;
+
+    // Split off an optional query string.
+    let (path, query) = match req.path.find('?') {
+        Some(i) => (&req.path[..i], &req.path[i + 1..]),
+        None => (req.path.as_str(), ""),
+    };
+
+    if path == "/v2/" {
+        if req.headers.get("authorization").is_none() {
+            let challenge = format!(
+                "Bearer realm=\"http://{}/token\",service=\"mock\"",
+                addr
+            );
+            respond(
+                &mut stream,
+                401,
+                "Unauthorized",
+                &[
+                    ("WWW-Authenticate", challenge.as_str()),
+                    ("Docker-Distribution-API-Version", "registry/2.0"),
+                ],
+                b"",
+            );
+        } else {
+            respond(
+                &mut stream,
+                200,
+                "OK",
+                &[("Docker-Distribution-API-Version", "registry/2.0")],
+                b"",
+            );
+        }
+        return;
+    }
+
+    if path == "/token" {
+        // Echo the requested scope back into the (opaque) token so tests
+        // can assert that the scope was honored.
+        let scope = query
+            .split('&')
+            .find(|kv| kv.starts_with("scope="))
+            .map(|kv| &kv[6..])
+            .unwrap_or("");
+        let body = json!({ "token": format!("mock-token:{}", scope) });
+        respond_json(&mut stream, 200, &serde_json::to_vec(&body).unwrap());
+        return;
+    }
+
+    // /v2/<name...>/manifests/<ref> and /v2/<name...>/blobs/<digest>
+    if path.starts_with("/v2/") {
+        let rest = &path["/v2/".len()..];
+        if let Some(idx) = rest.find("/manifests/") {
+            let name = &rest[..idx];
+            let reference = &rest[idx + "/manifests/".len()..];
+            serve_manifest(&mut stream, &req.method, name, reference, state);
+            return;
+        }
+        if let Some(idx) = rest.find("/blobs/uploads") {
+            let name = &rest[..idx];
+            let session = rest[idx + "/blobs/uploads".len()..].trim_start_matches('/');
+            serve_upload(&mut stream, &req, addr, name, session, query, state);
+            return;
+        }
+        if let Some(idx) = rest.find("/blobs/") {
+            let name = &rest[..idx];
+            let digest = &rest[idx + "/blobs/".len()..];
+            serve_blob(&mut stream, &req.method, name, digest, state);
+            return;
+        }
+        if rest.ends_with("/tags/list") {
+            let name = &rest[..rest.len() - "/tags/list".len()];
+            serve_tags(&mut stream, addr, name, query, state);
+            return;
+        }
+    }
+
+    respond(&mut stream, 404, "Not Found", &[], b"");
+}
+
+/// Serve a manifest by tag or digest.
+fn serve_manifest(
+    stream: &mut TcpStream,
+    method: &str,
+    name: &str,
+    reference: &str,
+    state: &Arc<Mutex<State>>,
+) {
+    let state = state.lock().unwrap();
+    match state.manifests.get(&format!("{}/{}", name, reference)) {
+        Some(obj) => {
+            let digest = digest_of(&obj.body);
+            let headers = [
+                ("Content-Type", obj.media_type.as_str()),
+                ("Docker-Content-Digest", digest.as_str()),
+            ];
+            let body: &[u8] = if method == "HEAD" { b"" } else { &obj.body };
+            respond(stream, 200, "OK", &headers, body);
+        }
+        None => respond(stream, 404, "Not Found", &[], b""),
+    }
+}
+
+/// Serve a blob by digest.
+fn serve_blob(
+    stream: &mut TcpStream,
+    method: &str,
+    _name: &str,
+    digest: &str,
+    state: &Arc<Mutex<State>>,
+) {
+    let state = state.lock().unwrap();
+    match state.blobs.get(digest) {
+        Some(obj) => {
+            let headers = [
+                ("Content-Type", obj.media_type.as_str()),
+                ("Docker-Content-Digest", digest),
+            ];
+            let body: &[u8] = if method == "HEAD" { b"" } else { &obj.body };
+            respond(stream, 200, "OK", &headers, body);
+        }
+        None => respond(stream, 404, "Not Found", &[], b""),
+    }
+}
+
+/// Drive the blob-upload dance: `POST` opens a session, `PATCH` appends a
+/// chunk, and `PUT ...?digest=` finalizes and stores the blob.
+fn serve_upload(
+    stream: &mut TcpStream,
+    req: &Request,
+    addr: &str,
+    name: &str,
+    session: &str,
+    query: &str,
+    state: &Arc<Mutex<State>>,
+) {
+    let mut state = state.lock().unwrap();
+    match req.method.as_str() {
+        "POST" => {
+            let id = UPLOAD_SEQ.fetch_add(1, Ordering::SeqCst).to_string();
+            state.uploads.insert(id.clone(), Vec::new());
+            let location = format!("http://{}/v2/{}/blobs/uploads/{}", addr, name, id);
+            respond(
+                stream,
+                202,
+                "Accepted",
+                &[("Location", location.as_str()), ("Range", "0-0")],
+                b"",
+            );
+        }
+        "PATCH" => {
+            let buf = match state.uploads.get_mut(session) {
+                Some(b) => b,
+                None => return respond(stream, 404, "Not Found", &[], b""),
+            };
+            buf.extend_from_slice(&req.body);
+            let range = format!("0-{}", buf.len().saturating_sub(1));
+            let location = format!("http://{}/v2/{}/blobs/uploads/{}", addr, name, session);
+            respond(
+                stream,
+                202,
+                "Accepted",
+                &[("Location", location.as_str()), ("Range", range.as_str())],
+                b"",
+            );
+        }
+        "PUT" => {
+            let mut buf = state.uploads.remove(session).unwrap_or_default();
+            buf.extend_from_slice(&req.body);
+            let digest = query
+                .split('&')
+                .find(|kv| kv.starts_with("digest="))
+                .map(|kv| kv["digest=".len()..].to_string())
+                .unwrap_or_else(|| digest_of(&buf));
+            state.blobs.insert(
+                digest.clone(),
+                Object {
+                    media_type: LAYER_GZIP.to_string(),
+                    body: buf,
+                },
+            );
+            respond(
+                stream,
+                201,
+                "Created",
+                &[("Docker-Content-Digest", digest.as_str())],
+                b"",
+            );
+        }
+        _ => respond(stream, 405, "Method Not Allowed", &[], b""),
+    }
+}
+
+/// Serve a `tags/list` page, following the `n`/`last` pagination params and
+/// advertising a `Link` header while more tags remain.
+fn serve_tags(stream: &mut TcpStream, addr: &str, name: &str, query: &str, state: &Arc<Mutex<State>>) {
+    let state = state.lock().unwrap();
+    let tags = match state.tags.get(name) {
+        Some(t) => t,
+        None => return respond(stream, 404, "Not Found", &[], b""),
+    };
+
+    let param = |key: &str| {
+        query
+            .split('&')
+            .find(|kv| kv.starts_with(&format!("{}=", key)))
+            .map(|kv| kv[key.len() + 1..].to_string())
+    };
+    let n: usize = param("n").and_then(|v| v.parse().ok()).unwrap_or(tags.len());
+    let last = param("last");
+
+    let start = match last {
+        Some(ref l) => tags.iter().position(|t| t == l).map(|i| i + 1).unwrap_or(0),
+        None => 0,
+    };
+    let page: Vec<&String> = tags.iter().skip(start).take(n).collect();
+
+    let body = json!({ "name": name, "tags": page });
+    let body = serde_json::to_vec(&body).unwrap();
+
+    let mut headers = vec![("Content-Type".to_string(), "application/json".to_string())];
+    if start + page.len() < tags.len() {
+        if let Some(last_tag) = page.last() {
+            let link = format!(
+                "<http://{}/v2/{}/tags/list?n={}&last={}>; rel=\"next\"",
+                addr, name, n, last_tag
+            );
+            headers.push(("Link".to_string(), link));
+        }
+    }
+    let header_refs: Vec<(&str, &str)> =
+        headers.iter().map(|(k, v)| (k.as_str(), v.as_str())).collect();
+    respond(stream, 200, "OK", &header_refs, &body);
+}
+
+/// Read a request line and headers off the wire.
+fn parse_request<R: BufRead>(reader: &mut R) -> Option<Request> {
+    let mut line = String::new();
+    if reader.read_line(&mut line).ok()? == 0 {
+        return None;
+    }
+    let mut parts = line.split_whitespace();
+    let method = parts.next()?.to_string();
+    let path = parts.next()?.to_string();
+
+    let mut headers = HashMap::new();
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header).ok()? == 0 {
+            break;
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some(i) = header.find(':') {
+            let key = header[..i].trim().to_ascii_lowercase();
+            let val = header[i + 1..].trim().to_string();
+            headers.insert(key, val);
+        }
+    }
+
+    let body = match headers.get("content-length").and_then(|v| v.parse::<usize>().ok()) {
+        Some(len) if len > 0 => {
+            let mut buf = vec![0u8; len];
+            reader.read_exact(&mut buf).ok()?;
+            buf
+        }
+        _ => Vec::new(),
+    };
+
+    Some(Request {
+        method,
+        path,
+        headers,
+        body,
+    })
+}
+
+/// Write a JSON response.
+fn respond_json(stream: &mut TcpStream, status: u16, body: &[u8]) {
+    respond(
+        stream,
+        status,
+        "OK",
+        &[("Content-Type", "application/json")],
+        body,
+    );
+}
+
+/// Write a raw HTTP/1.1 response and close the connection.
+fn respond(stream: &mut TcpStream, status: u16, reason: &str, headers: &[(&str, &str)], body: &[u8]) {
+    let mut out = format!("HTTP/1.1 {} {}\r\n", status, reason);
+    for (k, v) in headers {
+        out.push_str(&format!("{}: {}\r\n", k, v));
+    }
+    out.push_str(&format!("Content-Length: {}\r\n", body.len()));
+    out.push_str("Connection: close\r\n\r\n");
+    let _ = stream.write_all(out.as_bytes());
+    let _ = stream.write_all(body);
+    let _ = stream.flush();
+}
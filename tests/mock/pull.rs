@@ -0,0 +1,169 @@
+extern crate dkregistry;
+extern crate futures;
+extern crate serde_json;
+extern crate tokio;
+
+use self::tokio::runtime::current_thread::Runtime;
+use super::harness::{digest_of, MockRegistry};
+
+fn client(addr: &str) -> dkregistry::v2::Client {
+    dkregistry::v2::Client::configure()
+        .registry(addr)
+        .insecure_registry(true)
+        .username(None)
+        .password(None)
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn test_pull_schema2_end_to_end() {
+    let registry = MockRegistry::start();
+    let config = br#"{"architecture":"amd64","os":"linux"}"#;
+    let layer = b"layer-bytes";
+    let manifest_digest = registry.register_image("library/busybox", "latest", config, &[layer]);
+
+    let mut runtime = Runtime::new().unwrap();
+    let dclient = client(registry.address());
+
+    let kind = runtime
+        .block_on(dclient.has_manifest("library/busybox", "latest", None))
+        .unwrap();
+    assert_eq!(kind, Some(dkregistry::mediatypes::MediaTypes::ManifestV2S2));
+
+    let body = runtime
+        .block_on(dclient.get_manifest("library/busybox", "latest"))
+        .unwrap();
+    let manifest: dkregistry::v2::manifest::ManifestSchema2 =
+        serde_json::from_slice(&body).unwrap();
+    assert_eq!(manifest.config_digest(), digest_of(config));
+
+    let image_config = runtime
+        .block_on(dclient.get_config_blob("library/busybox", &manifest.config_digest()))
+        .unwrap();
+    assert_eq!(image_config.architecture, "amd64");
+    assert_eq!(image_config.os, "linux");
+
+    for layer_digest in manifest.get_layers() {
+        let blob = runtime
+            .block_on(dclient.get_verified_blob("library/busybox", &layer_digest))
+            .unwrap();
+        assert_eq!(blob, layer.to_vec());
+    }
+
+    // The manifest is also addressable (and verifiable) by digest.
+    assert!(runtime
+        .block_on(dclient.get_manifest("library/busybox", &manifest_digest))
+        .is_ok());
+}
+
+#[test]
+fn test_pull_multi_arch_selection() {
+    let registry = MockRegistry::start();
+    let cfg_amd = br#"{"architecture":"amd64","os":"linux"}"#;
+    let cfg_arm = br#"{"architecture":"arm64","os":"linux"}"#;
+    let amd = registry.register_image("library/busybox", "amd64", cfg_amd, &[b"a"]);
+    let arm = registry.register_image("library/busybox", "arm64", cfg_arm, &[b"b"]);
+    registry.register_manifest_list(
+        "library/busybox",
+        "latest",
+        &[("amd64", "linux", &amd), ("arm64", "linux", &arm)],
+    );
+
+    let mut runtime = Runtime::new().unwrap();
+    let dclient = client(registry.address());
+
+    let kind = runtime
+        .block_on(dclient.has_manifest("library/busybox", "latest", None))
+        .unwrap();
+    assert_eq!(kind, Some(dkregistry::mediatypes::MediaTypes::ManifestList));
+
+    let body = runtime
+        .block_on(dclient.get_manifest_for_platform("library/busybox", "latest", "arm64", "linux"))
+        .unwrap();
+    let manifest: dkregistry::v2::manifest::ManifestSchema2 =
+        serde_json::from_slice(&body).unwrap();
+    assert_eq!(manifest.config_digest(), digest_of(cfg_arm));
+}
+
+#[test]
+fn test_verified_blob_detects_tampering() {
+    let registry = MockRegistry::start();
+    // The blob is present under `claimed`, but its body hashes to something
+    // else, so fetching it drives `ContentDigest::verify` to a mismatch.
+    let claimed = digest_of(b"what-the-caller-expects");
+    registry.register_raw_blob(&claimed, b"tampered-bytes");
+
+    let mut runtime = Runtime::new().unwrap();
+    let dclient = client(registry.address());
+
+    let res = runtime.block_on(dclient.get_verified_blob("library/busybox", &claimed));
+    match res {
+        Err(e) => assert!(e.to_string().contains("digest")),
+        Ok(_) => panic!("expected a content-digest mismatch"),
+    }
+}
+
+#[test]
+fn test_authenticate_token_dance() {
+    let registry = MockRegistry::start();
+    registry.register_image("library/busybox", "latest", br#"{}"#, &[b"x"]);
+
+    let mut runtime = Runtime::new().unwrap();
+    let dclient = client(registry.address());
+
+    // Probe /v2/, parse the Bearer challenge, and exchange it for a token.
+    let authed = runtime
+        .block_on(dclient.authenticate(&["repository:library/busybox:pull"]))
+        .unwrap();
+
+    // The authenticated client can still resolve manifests.
+    let kind = runtime
+        .block_on(authed.has_manifest("library/busybox", "latest", None))
+        .unwrap();
+    assert_eq!(kind, Some(dkregistry::mediatypes::MediaTypes::ManifestV2S2));
+}
+
+#[test]
+fn test_get_tags_pagination() {
+    let registry = MockRegistry::start();
+    registry.register_tags("library/busybox", &["1.0", "1.1", "1.2", "latest"]);
+
+    let mut runtime = Runtime::new().unwrap();
+    let dclient = client(registry.address());
+
+    // A page size of 2 forces the client to follow the `Link` header across
+    // multiple pages before it sees every tag.
+    let tags = runtime
+        .block_on(dclient.get_tags("library/busybox", Some(2)).collect())
+        .unwrap();
+    assert_eq!(tags, vec!["1.0", "1.1", "1.2", "latest"]);
+}
+
+#[test]
+fn test_push_blob_round_trip() {
+    let registry = MockRegistry::start();
+    let body = b"a-freshly-built-layer";
+    let digest = digest_of(body);
+
+    let mut runtime = Runtime::new().unwrap();
+    let dclient = client(registry.address());
+
+    // The layer is absent until we upload it.
+    assert!(!runtime
+        .block_on(dclient.has_blob("library/busybox", &digest))
+        .unwrap());
+
+    let stored = runtime
+        .block_on(dclient.push_blob("library/busybox", body, &digest))
+        .unwrap();
+    assert_eq!(stored, digest);
+
+    assert!(runtime
+        .block_on(dclient.has_blob("library/busybox", &digest))
+        .unwrap());
+    let fetched = runtime
+        .block_on(dclient.get_verified_blob("library/busybox", &digest))
+        .unwrap();
+    assert_eq!(fetched, body.to_vec());
+}